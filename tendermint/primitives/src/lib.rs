@@ -4,10 +4,10 @@
 extern crate alloc;
 
 #[cfg(feature = "std")]
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use codec::{Codec, Decode, Encode};
-use sp_runtime::{ConsensusEngineId, RuntimeDebug};
+use sp_runtime::{traits::NumberFor, ConsensusEngineId, RuntimeDebug};
 use sp_std::vec::Vec;
 
 #[cfg(feature = "std")]
@@ -57,8 +57,39 @@ pub type SetId = u64;
 /// The round indicator.
 pub type RoundNumber = u64;
 
-/// A list of Grandpa authorities with associated weights.
-pub type AuthorityList = Vec<AuthorityId>;
+/// The weight of an authority.
+pub type AuthorityWeight = u64;
+
+/// A list of Tendermint authorities, along with their respective weights.
+pub type AuthorityList = Vec<(AuthorityId, AuthorityWeight)>;
+
+/// The encoding version of the authority list currently stored at
+/// `TMNT_AUTHORITIES_KEY`.
+///
+/// A versioned encoding lets light clients decode the stored authority set from a
+/// storage proof alone, and lets us change the on-disk format later without breaking
+/// clients that only know about older versions.
+#[cfg_attr(feature = "std", derive(Serialize))]
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum VersionedAuthorityList {
+    /// The initial version of the stored authority list.
+    #[codec(index = 1)]
+    V1(AuthorityList),
+}
+
+impl From<AuthorityList> for VersionedAuthorityList {
+    fn from(list: AuthorityList) -> Self {
+        VersionedAuthorityList::V1(list)
+    }
+}
+
+impl From<VersionedAuthorityList> for AuthorityList {
+    fn from(versioned: VersionedAuthorityList) -> Self {
+        match versioned {
+            VersionedAuthorityList::V1(list) => list,
+        }
+    }
+}
 
 // Struct to represent a scheduled change in the authority set, including the new set and a delay for activation.
 #[cfg_attr(feature = "std", derive(Serialize))]
@@ -220,7 +251,238 @@ where
     valid
 }
 
+/// Check a batch of message signatures at once.
+///
+/// Rather than verifying each `(message, id, signature, round, set_id)` tuple
+/// individually, this recomputes every localized payload and checks the whole batch
+/// against a single Ed25519 batch-verification equation, drawing a random 128-bit
+/// scalar per entry so a forger cannot craft invalid signatures that cancel each
+/// other out. This is considerably cheaper than individual verification when a node
+/// is catching up and has thousands of Prevote/Precommit signatures to check.
+///
+/// Returns `Ok(())` if every signature in the batch is valid. Otherwise, falls back
+/// to checking each message individually and returns the indices of the bad ones, so
+/// the caller still learns exactly which message failed.
+#[cfg(feature = "std")]
+pub fn check_message_signatures_batch<'a, H, N>(
+    messages: &[(
+        &'a messages::Message<H, N>,
+        &'a AuthorityId,
+        &'a AuthoritySignature,
+        RoundNumber,
+        SetId,
+    )],
+) -> Result<(), Vec<usize>>
+where
+    H: Encode,
+    N: Encode,
+{
+    use ed25519_dalek::{verify_batch, PublicKey, Signature};
+
+    if messages.is_empty() {
+        return Ok(());
+    }
+
+    let mut buf = Vec::new();
+    let mut payloads = Vec::with_capacity(messages.len());
+    let mut public_keys = Vec::with_capacity(messages.len());
+    let mut signatures = Vec::with_capacity(messages.len());
+
+    for (message, id, signature, round, set_id) in messages {
+        localized_payload_with_buffer(*round, *set_id, *message, &mut buf);
+        payloads.push(buf.clone());
+
+        let (public_key, sig) =
+            match (PublicKey::from_bytes(id.as_ref()), Signature::from_bytes(signature.as_ref()))
+            {
+                (Ok(public_key), Ok(sig)) => (public_key, sig),
+                _ => return Err(check_message_signatures_individually(messages)),
+            };
+
+        public_keys.push(public_key);
+        signatures.push(sig);
+    }
+
+    let payload_refs: sp_std::vec::Vec<&[u8]> = payloads.iter().map(|p| p.as_slice()).collect();
+
+    match verify_batch(&payload_refs, &signatures, &public_keys) {
+        Ok(()) => Ok(()),
+        Err(_) => Err(check_message_signatures_individually(messages)),
+    }
+}
+
+/// Verify each message signature individually, returning the indices of the ones
+/// that do not check out.
+#[cfg(feature = "std")]
+fn check_message_signatures_individually<H, N>(
+    messages: &[(
+        &messages::Message<H, N>,
+        &AuthorityId,
+        &AuthoritySignature,
+        RoundNumber,
+        SetId,
+    )],
+) -> Vec<usize>
+where
+    H: Encode,
+    N: Encode,
+{
+    messages
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (message, id, signature, round, set_id))| {
+            if check_message_signature(message, id, signature, *round, *set_id) {
+                None
+            } else {
+                Some(i)
+            }
+        })
+        .collect()
+}
+
+/// A Prevote or Precommit cast by a Tendermint authority, together with its signature.
+pub type SignedMessage<H, N> = messages::SignedMessage<H, N, AuthoritySignature, AuthorityId>;
+
+/// Proof of an equivocation (double-vote) by a Tendermint authority during a round.
+///
+/// Captures two conflicting `SignedMessage`s of the same kind (both Prevotes or both
+/// Precommits) cast by the same authority at the same height/round but targeting
+/// different block hashes.
+#[cfg_attr(feature = "std", derive(Serialize))]
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct Equivocation<H, N> {
+    /// The authority that produced both conflicting votes.
+    pub identity: AuthorityId,
+    /// The round number the equivocation took place in.
+    pub round_number: RoundNumber,
+    /// The authority set id the equivocation took place in.
+    pub set_id: SetId,
+    /// The first of the two conflicting signed messages.
+    pub first: SignedMessage<H, N>,
+    /// The second of the two conflicting signed messages.
+    pub second: SignedMessage<H, N>,
+}
+
+/// A proof of equivocation, ready to be reported to the runtime for slashing.
+#[cfg_attr(feature = "std", derive(Serialize))]
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct EquivocationProof<H, N> {
+    equivocation: Equivocation<H, N>,
+}
+
+impl<H, N> EquivocationProof<H, N> {
+    /// Create a new proof of equivocation.
+    pub fn new(equivocation: Equivocation<H, N>) -> Self {
+        EquivocationProof { equivocation }
+    }
+
+    /// Returns the set id at which the equivocation occurred.
+    pub fn set_id(&self) -> SetId {
+        self.equivocation.set_id
+    }
+
+    /// Returns the round number at which the equivocation occurred.
+    pub fn round(&self) -> RoundNumber {
+        self.equivocation.round_number
+    }
+
+    /// Returns the authority id of the equivocator.
+    pub fn offender(&self) -> &AuthorityId {
+        &self.equivocation.identity
+    }
+
+    /// Returns the equivocation itself.
+    pub fn equivocation(&self) -> &Equivocation<H, N> {
+        &self.equivocation
+    }
+}
+
+/// Returns true iff the two messages are of the same kind (both Prevotes or both
+/// Precommits), target the same height, and target different block hashes.
+///
+/// The height check matters: two honest, sequential votes from different heights
+/// will almost always have different target hashes too, so comparing hashes alone
+/// would misclassify them as an equivocation.
+fn are_conflicting<H: PartialEq, N: PartialEq>(
+    first: &messages::Message<H, N>,
+    second: &messages::Message<H, N>,
+) -> bool {
+    match (first, second) {
+        (messages::Message::Prevote(first), messages::Message::Prevote(second)) => {
+            first.target_number == second.target_number && first.target_hash != second.target_hash
+        }
+        (messages::Message::Precommit(first), messages::Message::Precommit(second)) => {
+            first.target_number == second.target_number && first.target_hash != second.target_hash
+        }
+        _ => false,
+    }
+}
+
+/// Check a proof of equivocation.
+///
+/// Returns `true` only when both signed messages are of the same kind, were cast in
+/// the same round and set by the alleged offender, target different block hashes, and
+/// both signatures verify correctly.
+pub fn check_equivocation_proof<H, N>(proof: EquivocationProof<H, N>) -> bool
+where
+    H: Clone + Encode + PartialEq,
+    N: Clone + Encode + PartialEq,
+{
+    let equivocation = proof.equivocation();
+    let first = &equivocation.first;
+    let second = &equivocation.second;
+
+    if first.id != equivocation.identity || second.id != equivocation.identity {
+        return false;
+    }
+
+    if !are_conflicting(&first.message, &second.message) {
+        return false;
+    }
+
+    let mut buf = Vec::new();
+    check_message_signature_with_buffer(
+        &first.message,
+        &equivocation.identity,
+        &first.signature,
+        equivocation.round_number,
+        equivocation.set_id,
+        &mut buf,
+    ) && check_message_signature_with_buffer(
+        &second.message,
+        &equivocation.identity,
+        &second.signature,
+        equivocation.round_number,
+        equivocation.set_id,
+        &mut buf,
+    )
+}
+
+/// Opaque type used to represent the key ownership proof at the runtime API boundary.
+///
+/// The inner value is not accessible, but the type can still be passed between runtime
+/// functions.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct OpaqueKeyOwnershipProof(Vec<u8>);
+
+impl OpaqueKeyOwnershipProof {
+    /// Create a new `OpaqueKeyOwnershipProof` using the given encoded representation.
+    pub fn new(inner: Vec<u8>) -> OpaqueKeyOwnershipProof {
+        OpaqueKeyOwnershipProof(inner)
+    }
+
+    /// Try to decode this `OpaqueKeyOwnershipProof` into the given concrete key
+    /// ownership proof type.
+    pub fn decode<T: Decode>(self) -> Option<T> {
+        codec::Decode::decode(&mut &self.0[..]).ok()
+    }
+}
+
 /// Localizes the message to the given set and round and signs the payload.
+///
+/// This performs no double-signing protection; prefer [`sign_message_guarded`] when a
+/// `ConsensusStateStore` is available, which refuses to sign messages that would
+/// regress the validator's last-signed `ConsensusState`.
 #[cfg(feature = "std")]
 pub fn sign_message<H, N>(
     keystore: KeystorePtr,
@@ -248,12 +510,247 @@ where
     })
 }
 
+/// The stage of Tendermint consensus a message belongs to. Ordered so that
+/// `Propose < Prevote < Precommit`, matching the order in which they occur within a
+/// round.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug)]
+pub enum Step {
+    /// A block proposal.
+    Propose,
+    /// A prevote for a proposed block.
+    Prevote,
+    /// A precommit for a proposed block.
+    Precommit,
+}
+
+#[cfg(feature = "std")]
+fn message_step<H, N>(message: &messages::Message<H, N>) -> Step {
+    match message {
+        messages::Message::Prevote(_) => Step::Prevote,
+        messages::Message::Precommit(_) => Step::Precommit,
+    }
+}
+
+#[cfg(feature = "std")]
+fn message_target_number<H, N: Clone>(message: &messages::Message<H, N>) -> N {
+    match message {
+        messages::Message::Prevote(prevote) => prevote.target_number.clone(),
+        messages::Message::Precommit(precommit) => precommit.target_number.clone(),
+    }
+}
+
+/// The last `(height, round, step)` a validator has signed a message for.
+///
+/// This is the high-water mark used by remote Tendermint signers to prevent a
+/// restarted or duplicated validator from double-signing: a new message may only be
+/// signed if its state is strictly greater than the last persisted one. The type is
+/// (de)serializable so it can be written to durable storage next to the keystore.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug)]
+pub struct ConsensusState<N> {
+    /// The block height.
+    pub height: N,
+    /// The round within the height.
+    pub round: RoundNumber,
+    /// The step within the round.
+    pub step: Step,
+}
+
+/// Storage for the last `ConsensusState` a validator has signed, used by
+/// [`sign_message_guarded`] to guard against double-signing across restarts.
+#[cfg(feature = "std")]
+pub trait ConsensusStateStore<N> {
+    /// Load the last state the validator signed a message for, if any.
+    fn load(&self) -> Option<ConsensusState<N>>;
+    /// Persist the state reached after producing a new signature.
+    fn save(&self, state: ConsensusState<N>);
+}
+
+/// The double-sign guard in [`sign_message_guarded`] refused to produce a signature
+/// because doing so would not strictly advance past the last-signed
+/// `ConsensusState`.
+///
+/// Unlike an ordinary signing failure (a routine `None`), this indicates an actual
+/// double-signing attempt and should be treated as a safety violation by the caller.
+#[cfg(feature = "std")]
+#[derive(Clone, Eq, PartialEq, RuntimeDebug)]
+pub struct DoubleSignGuardTripped;
+
+/// Localizes the message to the given set and round and signs the payload, refusing
+/// to sign if doing so would regress the validator's last-signed `ConsensusState`.
+///
+/// Returns `Err(DoubleSignGuardTripped)` if the message's `(height, round, step)` is
+/// not strictly greater than the last state persisted in `store` — this is a safety
+/// violation and distinct from `Ok(None)`, which is the routine case of the
+/// underlying keystore failing to produce a signature.
+#[cfg(feature = "std")]
+pub fn sign_message_guarded<H, N>(
+    keystore: KeystorePtr,
+    store: &dyn ConsensusStateStore<N>,
+    message: messages::Message<H, N>,
+    public: AuthorityId,
+    round: RoundNumber,
+    set_id: SetId,
+) -> Result<Option<messages::SignedMessage<H, N, AuthoritySignature, AuthorityId>>, DoubleSignGuardTripped>
+where
+    H: Encode,
+    N: Clone + Ord + Encode,
+{
+    let new_state = ConsensusState {
+        height: message_target_number(&message),
+        round,
+        step: message_step(&message),
+    };
+
+    if let Some(last_state) = store.load() {
+        if new_state <= last_state {
+            debug!(
+                target: "afg",
+                "Refusing to sign message from {:?}: state would not advance past the last signed state",
+                public,
+            );
+            return Err(DoubleSignGuardTripped);
+        }
+    }
+
+    let signed = match sign_message(keystore, message, public, round, set_id) {
+        Some(signed) => signed,
+        None => return Ok(None),
+    };
+    store.save(new_state);
+    Ok(Some(signed))
+}
+
+/// A commit message which aggregates Precommit `SignedMessage`s for a given block,
+/// all cast at the same `(round, set_id)`.
+#[cfg_attr(feature = "std", derive(Serialize))]
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct Commit<H, N> {
+    /// The target block hash being committed.
+    pub target_hash: H,
+    /// The target block number being committed.
+    pub target_number: N,
+    /// The Precommit signed messages making up the commit.
+    pub precommits: Vec<SignedMessage<H, N>>,
+}
+
+/// A self-contained finality proof for a block, built from a `Commit` of Precommits
+/// cast by the Tendermint authority set at a given round and set id.
+///
+/// This can be gossiped or stored as a block justification and checked by light
+/// clients holding only the versioned authority list, without needing an execution
+/// proof.
+#[cfg_attr(feature = "std", derive(Serialize))]
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct TendermintJustification<H, N> {
+    /// The round the commit was produced in.
+    pub round: RoundNumber,
+    /// The authority set id the commit was produced by.
+    pub set_id: SetId,
+    /// The aggregated Precommit votes for the finalized block.
+    pub commit: Commit<H, N>,
+}
+
+impl<H: Clone, N: Clone> TendermintJustification<H, N> {
+    /// The hash of the finalized block this justification is for.
+    pub fn target_hash(&self) -> H {
+        self.commit.target_hash.clone()
+    }
+
+    /// The number of the finalized block this justification is for.
+    pub fn target_number(&self) -> N {
+        self.commit.target_number.clone()
+    }
+}
+
+/// Reasons a `Commit` can fail to verify.
+#[derive(Clone, Eq, PartialEq, RuntimeDebug)]
+pub enum CommitValidationError {
+    /// A precommit is not of the Precommit message kind.
+    NotAPrecommit,
+    /// A precommit targets a different block than the commit as a whole.
+    TargetMismatch,
+    /// A precommit was cast by an authority not in the given authority set.
+    UnknownAuthority,
+    /// Two or more precommits were cast by the same authority.
+    DuplicateAuthority,
+    /// A precommit carries an invalid signature.
+    BadSignature,
+    /// The summed weight of valid precommits does not exceed 2/3 of the total weight.
+    InsufficientWeight,
+}
+
+/// Verify that a `TendermintJustification` proves finality of its target block under
+/// the given weighted `AuthorityList`.
+///
+/// Recomputes each precommit's localized payload, checks every signature, ensures
+/// every signer is a distinct known authority, and confirms the summed weight of
+/// valid precommits exceeds 2/3 of the total weight.
+pub fn verify_commit<H, N>(
+    justification: &TendermintJustification<H, N>,
+    authorities: &AuthorityList,
+) -> Result<(), CommitValidationError>
+where
+    H: Clone + Encode + PartialEq,
+    N: Clone + Encode + PartialEq,
+{
+    let total_weight: u128 = authorities.iter().map(|(_, weight)| *weight as u128).sum();
+
+    let mut signers = Vec::new();
+    let mut signed_weight: u128 = 0;
+    let mut buf = Vec::new();
+
+    for precommit in &justification.commit.precommits {
+        match &precommit.message {
+            messages::Message::Precommit(inner) => {
+                if inner.target_hash != justification.commit.target_hash
+                    || inner.target_number != justification.commit.target_number
+                {
+                    return Err(CommitValidationError::TargetMismatch);
+                }
+            }
+            _ => return Err(CommitValidationError::NotAPrecommit),
+        }
+
+        let weight = authorities
+            .iter()
+            .find(|(id, _)| *id == precommit.id)
+            .map(|(_, weight)| *weight)
+            .ok_or(CommitValidationError::UnknownAuthority)?;
+
+        if signers.contains(&precommit.id) {
+            return Err(CommitValidationError::DuplicateAuthority);
+        }
+        signers.push(precommit.id.clone());
+
+        if !check_message_signature_with_buffer(
+            &precommit.message,
+            &precommit.id,
+            &precommit.signature,
+            justification.round,
+            justification.set_id,
+            &mut buf,
+        ) {
+            return Err(CommitValidationError::BadSignature);
+        }
+
+        signed_weight += weight as u128;
+    }
+
+    if signed_weight * 3 <= total_weight * 2 {
+        return Err(CommitValidationError::InsufficientWeight);
+    }
+
+    Ok(())
+}
+
 sp_api::decl_runtime_apis! {
     /// APIs for integrating the TENDERMINT finality gadget into runtimes.
     /// This should be implemented on the runtime side.
 
     /// The consensus protocol will coordinate the handoff externally.
-    #[api_version(3)]
+    #[api_version(4)]
     pub trait TendermintApi {
         /// Get the current TENDERMINT authorities and weights. This should not change except
         /// for when changes are scheduled and the corresponding delay has passed.
@@ -264,5 +761,365 @@ sp_api::decl_runtime_apis! {
         fn tendermint_authorities() -> AuthorityList;
         /// Get current TENDERMINT authority set id.
         fn current_set_id() -> SetId;
+
+        /// Submits an unsigned extrinsic to report an equivocation. The caller must
+        /// provide the equivocation proof and a key ownership proof (should be
+        /// obtained using `generate_key_ownership_proof`). The extrinsic will be
+        /// unsigned and should only be accepted for local authorship (not gossiped
+        /// to the network). This method returns `None` when creation of the
+        /// extrinsic fails, e.g. if equivocation reporting is disabled for the
+        /// given runtime. Only useful in an offchain context.
+        fn submit_report_equivocation_unsigned_extrinsic(
+            equivocation_proof: EquivocationProof<Block::Hash, NumberFor<Block>>,
+            key_owner_proof: OpaqueKeyOwnershipProof,
+        ) -> Option<()>;
+
+        /// Generates a proof of key ownership for the given authority in the given
+        /// set. An example usage of this function is coupled with the session
+        /// historical module to prove that a given authority key is tied to a
+        /// given staking identity during a specific session. Proofs of key
+        /// ownership are necessary for submitting equivocation reports.
+        ///
+        /// NOTE: even though the API takes a `set_id` as parameter the current
+        /// implementations ignore this parameter and instead rely on this method
+        /// being called at the correct block height, i.e. any point at which the
+        /// given set id is live on-chain.
+        fn generate_key_ownership_proof(
+            set_id: SetId,
+            authority_id: AuthorityId,
+        ) -> Option<OpaqueKeyOwnershipProof>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use finality_tendermint::messages::{Precommit, Prevote};
+    use sp_core::Pair as _;
+
+    fn signed_precommit(
+        pair: &AuthorityPair,
+        target_hash: u64,
+        target_number: u64,
+        round: RoundNumber,
+        set_id: SetId,
+    ) -> SignedMessage<u64, u64> {
+        let message = messages::Message::Precommit(Precommit { target_hash, target_number });
+        let payload = localized_payload(round, set_id, &message);
+        let signature = pair.sign(&payload).into();
+        messages::SignedMessage { message, signature, id: pair.public() }
+    }
+
+    fn signed_prevote(
+        pair: &AuthorityPair,
+        target_hash: u64,
+        target_number: u64,
+        round: RoundNumber,
+        set_id: SetId,
+    ) -> SignedMessage<u64, u64> {
+        let message = messages::Message::Prevote(Prevote { target_hash, target_number });
+        let payload = localized_payload(round, set_id, &message);
+        let signature = pair.sign(&payload).into();
+        messages::SignedMessage { message, signature, id: pair.public() }
+    }
+
+    #[test]
+    fn versioned_authority_list_roundtrips_through_scale_codec() {
+        let pairs: Vec<_> = (1u8..=2).map(|seed| AuthorityPair::from_seed(&[seed; 32])).collect();
+        let list: AuthorityList = pairs.iter().map(|pair| (pair.public(), 5u64)).collect();
+
+        let versioned: VersionedAuthorityList = list.clone().into();
+        let encoded = versioned.encode();
+        let decoded = VersionedAuthorityList::decode(&mut &encoded[..])
+            .expect("a V1-encoded authority list decodes back");
+
+        assert_eq!(decoded, versioned);
+        assert_eq!(AuthorityList::from(decoded), list);
+    }
+
+    #[test]
+    fn batch_verification_all_valid() {
+        let pairs: Vec<_> = (1u8..=4).map(|seed| AuthorityPair::from_seed(&[seed; 32])).collect();
+        let signed: Vec<_> = pairs
+            .iter()
+            .enumerate()
+            .map(|(i, pair)| signed_precommit(pair, i as u64, 10, 3, 7))
+            .collect();
+
+        let entries: Vec<_> =
+            signed.iter().map(|s| (&s.message, &s.id, &s.signature, 3u64, 7u64)).collect();
+
+        assert_eq!(check_message_signatures_batch(&entries), Ok(()));
+    }
+
+    #[test]
+    fn batch_verification_reports_bad_index_via_fallback() {
+        let pairs: Vec<_> = (1u8..=4).map(|seed| AuthorityPair::from_seed(&[seed; 32])).collect();
+        let mut signed: Vec<_> = pairs
+            .iter()
+            .enumerate()
+            .map(|(i, pair)| signed_precommit(pair, i as u64, 10, 3, 7))
+            .collect();
+
+        // Swap in a signature from a different message so entry 2 no longer checks
+        // out, while every other entry stays valid.
+        signed[2].signature = signed_precommit(&pairs[0], 99, 10, 3, 7).signature;
+
+        let entries: Vec<_> =
+            signed.iter().map(|s| (&s.message, &s.id, &s.signature, 3u64, 7u64)).collect();
+
+        assert_eq!(check_message_signatures_batch(&entries), Err(vec![2]));
+    }
+
+    #[test]
+    fn batch_verification_empty_slice_is_ok() {
+        let entries: Vec<(
+            &messages::Message<u64, u64>,
+            &AuthorityId,
+            &AuthoritySignature,
+            RoundNumber,
+            SetId,
+        )> = Vec::new();
+
+        assert_eq!(check_message_signatures_batch(&entries), Ok(()));
+    }
+
+    #[test]
+    fn batch_verification_falls_back_on_malformed_public_key() {
+        let pair = AuthorityPair::from_seed(&[1; 32]);
+        let good = signed_precommit(&pair, 1, 10, 3, 7);
+
+        // y = 2 is not the y-coordinate of any Edwards curve point, so
+        // `PublicKey::from_bytes` must fail for it; the batch check must fall back
+        // to per-message verification instead of panicking, and must still report
+        // the correct (second) index as bad.
+        let mut bad_key = [0u8; 32];
+        bad_key[0] = 2;
+        let bad_id: AuthorityId = sp_core::ed25519::Public::from_raw(bad_key).into();
+        let entries = [
+            (&good.message, &good.id, &good.signature, 3u64, 7u64),
+            (&good.message, &bad_id, &good.signature, 3u64, 7u64),
+        ];
+
+        assert_eq!(check_message_signatures_batch(&entries), Err(vec![1]));
+    }
+
+    fn equivocation(
+        pair: &AuthorityPair,
+        first: SignedMessage<u64, u64>,
+        second: SignedMessage<u64, u64>,
+    ) -> EquivocationProof<u64, u64> {
+        EquivocationProof::new(Equivocation {
+            identity: pair.public(),
+            round_number: 3,
+            set_id: 7,
+            first,
+            second,
+        })
+    }
+
+    #[test]
+    fn same_height_different_hash_is_an_equivocation() {
+        let pair = AuthorityPair::from_seed(&[1; 32]);
+        let first = signed_precommit(&pair, 1, 10, 3, 7);
+        let second = signed_precommit(&pair, 2, 10, 3, 7);
+
+        assert!(check_equivocation_proof(equivocation(&pair, first, second)));
+    }
+
+    #[test]
+    fn different_height_is_not_an_equivocation() {
+        // Two honest, sequential votes from different heights will usually also have
+        // different target hashes; this must not be mistaken for an equivocation.
+        let pair = AuthorityPair::from_seed(&[1; 32]);
+        let first = signed_precommit(&pair, 1, 10, 3, 7);
+        let second = signed_precommit(&pair, 2, 11, 3, 7);
+
+        assert!(!check_equivocation_proof(equivocation(&pair, first, second)));
+    }
+
+    #[test]
+    fn same_height_same_hash_is_not_an_equivocation() {
+        let pair = AuthorityPair::from_seed(&[1; 32]);
+        let first = signed_precommit(&pair, 1, 10, 3, 7);
+        let second = signed_precommit(&pair, 1, 10, 3, 7);
+
+        assert!(!check_equivocation_proof(equivocation(&pair, first, second)));
+    }
+
+    #[test]
+    fn verify_commit_rejects_commit_at_exactly_two_thirds() {
+        let pairs: Vec<_> = (1u8..=3).map(|seed| AuthorityPair::from_seed(&[seed; 32])).collect();
+        let authorities: AuthorityList = pairs.iter().map(|pair| (pair.public(), 1u64)).collect();
+
+        // Only 2 of the 3 equally-weighted authorities sign: 2/3 of the total weight
+        // is not a strict majority over the threshold and must be rejected.
+        let commit = Commit {
+            target_hash: 1u64,
+            target_number: 10u64,
+            precommits: pairs[..2].iter().map(|pair| signed_precommit(pair, 1, 10, 3, 7)).collect(),
+        };
+        let justification = TendermintJustification { round: 3, set_id: 7, commit };
+
+        assert_eq!(
+            verify_commit(&justification, &authorities),
+            Err(CommitValidationError::InsufficientWeight),
+        );
+    }
+
+    #[test]
+    fn verify_commit_accepts_commit_above_two_thirds() {
+        let pairs: Vec<_> = (1u8..=3).map(|seed| AuthorityPair::from_seed(&[seed; 32])).collect();
+        let authorities: AuthorityList = pairs.iter().map(|pair| (pair.public(), 1u64)).collect();
+
+        let commit = Commit {
+            target_hash: 1u64,
+            target_number: 10u64,
+            precommits: pairs.iter().map(|pair| signed_precommit(pair, 1, 10, 3, 7)).collect(),
+        };
+        let justification = TendermintJustification { round: 3, set_id: 7, commit };
+
+        assert_eq!(verify_commit(&justification, &authorities), Ok(()));
+    }
+
+    #[test]
+    fn verify_commit_rejects_unknown_authority() {
+        let signer = AuthorityPair::from_seed(&[1; 32]);
+        let outsider = AuthorityPair::from_seed(&[9; 32]);
+        let authorities: AuthorityList = vec![(signer.public(), 1u64)];
+
+        let commit = Commit {
+            target_hash: 1u64,
+            target_number: 10u64,
+            precommits: vec![signed_precommit(&outsider, 1, 10, 3, 7)],
+        };
+        let justification = TendermintJustification { round: 3, set_id: 7, commit };
+
+        assert_eq!(
+            verify_commit(&justification, &authorities),
+            Err(CommitValidationError::UnknownAuthority),
+        );
+    }
+
+    #[test]
+    fn verify_commit_rejects_non_precommit_message() {
+        let pair = AuthorityPair::from_seed(&[1; 32]);
+        let authorities: AuthorityList = vec![(pair.public(), 1u64)];
+
+        let commit = Commit {
+            target_hash: 1u64,
+            target_number: 10u64,
+            precommits: vec![signed_prevote(&pair, 1, 10, 3, 7)],
+        };
+        let justification = TendermintJustification { round: 3, set_id: 7, commit };
+
+        assert_eq!(
+            verify_commit(&justification, &authorities),
+            Err(CommitValidationError::NotAPrecommit),
+        );
+    }
+
+    #[test]
+    fn verify_commit_rejects_precommit_targeting_a_different_block() {
+        let pair = AuthorityPair::from_seed(&[1; 32]);
+        let authorities: AuthorityList = vec![(pair.public(), 1u64)];
+
+        let commit = Commit {
+            target_hash: 1u64,
+            target_number: 10u64,
+            // Signed for a different block than the commit claims to be for.
+            precommits: vec![signed_precommit(&pair, 2, 10, 3, 7)],
+        };
+        let justification = TendermintJustification { round: 3, set_id: 7, commit };
+
+        assert_eq!(
+            verify_commit(&justification, &authorities),
+            Err(CommitValidationError::TargetMismatch),
+        );
+    }
+
+    #[test]
+    fn verify_commit_rejects_duplicate_signer() {
+        let pair = AuthorityPair::from_seed(&[1; 32]);
+        let authorities: AuthorityList = vec![(pair.public(), 1u64)];
+
+        let commit = Commit {
+            target_hash: 1u64,
+            target_number: 10u64,
+            precommits: vec![
+                signed_precommit(&pair, 1, 10, 3, 7),
+                signed_precommit(&pair, 1, 10, 3, 7),
+            ],
+        };
+        let justification = TendermintJustification { round: 3, set_id: 7, commit };
+
+        assert_eq!(
+            verify_commit(&justification, &authorities),
+            Err(CommitValidationError::DuplicateAuthority),
+        );
+    }
+
+    #[test]
+    fn verify_commit_rejects_bad_signature() {
+        let pair = AuthorityPair::from_seed(&[1; 32]);
+        let authorities: AuthorityList = vec![(pair.public(), 1u64)];
+
+        let mut precommit = signed_precommit(&pair, 1, 10, 3, 7);
+        // Swap in a signature produced for a different payload so it no longer
+        // matches this precommit.
+        precommit.signature = signed_precommit(&pair, 99, 10, 3, 7).signature;
+
+        let commit = Commit {
+            target_hash: 1u64,
+            target_number: 10u64,
+            precommits: vec![precommit],
+        };
+        let justification = TendermintJustification { round: 3, set_id: 7, commit };
+
+        assert_eq!(
+            verify_commit(&justification, &authorities),
+            Err(CommitValidationError::BadSignature),
+        );
+    }
+
+    #[test]
+    fn sign_message_guarded_rejects_regression_and_allows_progress() {
+        use std::{cell::RefCell, sync::Arc};
+
+        struct InMemoryStore(RefCell<Option<ConsensusState<u64>>>);
+
+        impl ConsensusStateStore<u64> for InMemoryStore {
+            fn load(&self) -> Option<ConsensusState<u64>> {
+                *self.0.borrow()
+            }
+            fn save(&self, state: ConsensusState<u64>) {
+                *self.0.borrow_mut() = Some(state);
+            }
+        }
+
+        let keystore: KeystorePtr = Arc::new(sp_keystore::testing::MemoryKeystore::new());
+        let public: AuthorityId = keystore
+            .ed25519_generate_new(AuthorityId::ID, None)
+            .expect("keystore can generate a key")
+            .into();
+        let store = InMemoryStore(RefCell::new(None));
+
+        let message =
+            |target_number: u64| messages::Message::Precommit(Precommit { target_hash: 1u64, target_number });
+
+        sign_message_guarded(keystore.clone(), &store, message(1), public.clone(), 0, 0)
+            .expect("guard should not trip")
+            .expect("keystore holds the key, signing should succeed");
+
+        // Re-signing the very same height/round/step must be refused.
+        assert!(matches!(
+            sign_message_guarded(keystore.clone(), &store, message(1), public.clone(), 0, 0),
+            Err(DoubleSignGuardTripped),
+        ));
+
+        // A strictly later height is fine.
+        assert!(sign_message_guarded(keystore, &store, message(2), public, 0, 0)
+            .expect("guard should not trip")
+            .is_some());
     }
 }